@@ -41,8 +41,11 @@ type Meta = Option<i32>;
 
 impl Metadata<EasyMath> for Meta {
     type Error = ();
-    fn merge(&self, other: &Self) -> Self {
-        self.clone().and(other.clone())
+    fn merge(&self, other: &Self) -> (Self, DidMerge) {
+        merge_option(self.clone(), other.clone(), |a, b| {
+            assert_eq!(a, b);
+            (a, DidMerge(false, false))
+        })
     }
     fn make(enode: ENode<EasyMath, &Self>) -> Self {
          let c = |i: usize| enode.children[i].clone();
@@ -89,9 +92,15 @@ pub trait Metadata<L>: Sized + Debug {
     /// Defines how to merge two [`Metadata`]s when their containing
     /// [`EClass`]es merge.
     ///
+    /// Returns the merged metadata along with a [`DidMerge`] recording
+    /// whether it actually changed relative to `self` and to `other`, so
+    /// that callers can skip re-analyzing eclasses whose metadata didn't
+    /// move.
+    ///
     /// [`Metadata`]: trait.Metadata.html
     /// [`EClass`]: struct.EClass.html
-    fn merge(&self, other: &Self) -> Self;
+    /// [`DidMerge`]: struct.DidMerge.html
+    fn merge(&self, other: &Self) -> (Self, DidMerge);
 
     /// Makes a new [`Metadata`] given an operator and its children
     /// [`Metadata`].
@@ -111,7 +120,9 @@ pub trait Metadata<L>: Sized + Debug {
 
 impl<L: Language> Metadata<L> for () {
     type Error = std::convert::Infallible;
-    fn merge(&self, _other: &()) {}
+    fn merge(&self, _other: &()) -> ((), DidMerge) {
+        ((), DidMerge(false, false))
+    }
     fn make(_enode: ENode<L, &()>) {}
 }
 
@@ -149,13 +160,74 @@ impl<L, M> EClass<L, M> {
     }
 }
 
+/// The result of a [`Metadata::merge`], recording whether the merge
+/// actually changed anything.
+///
+/// The first field is `true` if the merged metadata differs from `to`
+/// (the metadata already on the surviving eclass); the second is `true` if
+/// it differs from `from` (the metadata on the eclass being merged in).
+/// `DidMerge(false, false)` means the merge was a no-op, which lets the
+/// union/rebuild loop skip re-canonicalizing and re-analyzing parents and
+/// lets the merge worklist short-circuit.
+///
+/// [`Metadata::merge`]: trait.Metadata.html#tymethod.merge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DidMerge(pub bool, pub bool);
+
+/// Merges two `Option<T>`s for use inside a [`Metadata::merge`]
+/// implementation.
+///
+/// `Some` wins over `None`; when both sides hold a value, `merge_same` is
+/// called to combine them and its [`DidMerge`] is returned as-is. This
+/// covers the common "constant folding" merge shape, like the `Option<i32>`
+/// example above, without writing the `DidMerge` bookkeeping by hand.
+///
+/// [`Metadata::merge`]: trait.Metadata.html#tymethod.merge
+pub fn merge_option<T>(
+    to: Option<T>,
+    from: Option<T>,
+    merge_same: impl FnOnce(T, T) -> (T, DidMerge),
+) -> (Option<T>, DidMerge) {
+    match (to, from) {
+        (None, None) => (None, DidMerge(false, false)),
+        (Some(a), None) => (Some(a), DidMerge(false, true)),
+        (None, Some(b)) => (Some(b), DidMerge(true, false)),
+        (Some(a), Some(b)) => {
+            let (merged, did_merge) = merge_same(a, b);
+            (Some(merged), did_merge)
+        }
+    }
+}
+
+/// Merges two values by keeping the larger one, for lattices that should
+/// only grow (e.g. a known lower bound).
+pub fn merge_max<T: PartialOrd>(to: T, from: T) -> (T, DidMerge) {
+    if to >= from {
+        let did_merge = DidMerge(false, to > from);
+        (to, did_merge)
+    } else {
+        (from, DidMerge(true, false))
+    }
+}
+
+/// Merges two values by keeping the smaller one, for cost-style lattices
+/// like the `cost: usize` field in `tests/math.rs`'s `Meta`.
+pub fn merge_min<T: PartialOrd>(to: T, from: T) -> (T, DidMerge) {
+    if to <= from {
+        let did_merge = DidMerge(false, to < from);
+        (to, did_merge)
+    } else {
+        (from, DidMerge(true, false))
+    }
+}
+
 impl<L: Language, M: Metadata<L>> Value for EClass<L, M> {
     type Error = std::convert::Infallible;
     fn merge<K: Key>(
         _unionfind: &mut UnionFind<K, Self>,
         to: Self,
         from: Self,
-    ) -> Result<Self, Self::Error> {
+    ) -> Result<(Self, DidMerge), Self::Error> {
         let mut less = to.nodes;
         let mut more = from.nodes;
 
@@ -166,10 +238,12 @@ impl<L: Language, M: Metadata<L>> Value for EClass<L, M> {
 
         more.extend(less);
 
+        let (metadata, did_merge) = to.metadata.merge(&from.metadata);
+
         let mut eclass = EClass {
             id: to.id,
             nodes: more,
-            metadata: to.metadata.merge(&from.metadata),
+            metadata,
             #[cfg(feature = "parent-pointers")]
             parents: {
                 let mut parents = to.parents;
@@ -179,6 +253,6 @@ impl<L: Language, M: Metadata<L>> Value for EClass<L, M> {
         };
 
         M::modify(&mut eclass);
-        Ok(eclass)
+        Ok((eclass, did_merge))
     }
 }
\ No newline at end of file
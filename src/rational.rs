@@ -0,0 +1,238 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{merge_option, DidMerge, EClass, ENode, Language, Metadata};
+
+/// An exact rational number.
+///
+/// `Rational` is meant as a constant type for [`Metadata`] implementations
+/// that fold constants through `+ - * / ^(integer)` without losing
+/// precision the way a float like `NotNan<f64>` does. Values are always
+/// kept normalized: `num` and `denom` have no common factor, `denom` is
+/// never `0`, and the sign lives entirely on `num`.
+///
+/// [`Metadata`]: trait.Metadata.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i64,
+    denom: u64,
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Rational {
+    /// Creates a new, normalized `Rational`.
+    ///
+    /// Returns `None` if `denom` is `0`, since there's no sound way to
+    /// represent that value.
+    pub fn new(num: i64, denom: u64) -> Option<Self> {
+        if denom == 0 {
+            return None;
+        }
+        let g = gcd(num.unsigned_abs(), denom);
+        let g = if g == 0 { 1 } else { g };
+        Some(Self {
+            num: num / g as i64,
+            denom: denom / g,
+        })
+    }
+
+    /// Returns `true` if this value has no fractional part.
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    /// Returns this value as an `i64`, if it's an integer.
+    pub fn to_i64(&self) -> Option<i64> {
+        self.is_integer().then(|| self.num)
+    }
+
+    /// The multiplicative inverse, or `None` if `self` is zero or the
+    /// inverse's numerator doesn't fit in an `i64`.
+    pub fn reciprocal(&self) -> Option<Self> {
+        let denom = i64::try_from(self.denom).ok()?;
+        if self.num == 0 {
+            None
+        } else if self.num < 0 {
+            Some(Self {
+                num: -denom,
+                denom: self.num.unsigned_abs(),
+            })
+        } else {
+            Some(Self {
+                num: denom,
+                denom: self.num as u64,
+            })
+        }
+    }
+
+    pub fn checked_add(&self, other: Self) -> Option<Self> {
+        let denom = lcm(self.denom, other.denom)?;
+        let a = self.num.checked_mul(i64::try_from(denom / self.denom).ok()?)?;
+        let b = other.num.checked_mul(i64::try_from(denom / other.denom).ok()?)?;
+        Self::new(a.checked_add(b)?, denom)
+    }
+
+    pub fn checked_sub(&self, other: Self) -> Option<Self> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self {
+            num: self.num.checked_neg()?,
+            denom: self.denom,
+        })
+    }
+
+    pub fn checked_mul(&self, other: Self) -> Option<Self> {
+        let num = self.num.checked_mul(other.num)?;
+        let denom = self.denom.checked_mul(other.denom)?;
+        Self::new(num, denom)
+    }
+
+    pub fn checked_div(&self, other: Self) -> Option<Self> {
+        self.checked_mul(other.reciprocal()?)
+    }
+
+    /// Raises `self` to an integer power, exactly.
+    pub fn checked_pow(&self, exp: i64) -> Option<Self> {
+        if exp == 0 {
+            return Self::new(1, 1);
+        }
+        let (base, exp) = if exp < 0 {
+            (self.reciprocal()?, exp.unsigned_abs())
+        } else {
+            (*self, exp.unsigned_abs())
+        };
+        // `0`, `1` and `-1` never overflow, so the loop below would
+        // otherwise spin for `exp` iterations on inputs like
+        // `(pow 1 1000000000)`.
+        if base.num == 0 {
+            return Self::new(0, 1);
+        }
+        if base.denom == 1 && base.num == 1 {
+            return Some(base);
+        }
+        if base.denom == 1 && base.num == -1 {
+            return Some(if exp % 2 == 0 { Self::from(1) } else { base });
+        }
+        let mut acc = Self::new(1, 1)?;
+        for _ in 0..exp {
+            acc = acc.checked_mul(base)?;
+        }
+        Some(acc)
+    }
+}
+
+fn lcm(a: u64, b: u64) -> Option<u64> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+impl From<i64> for Rational {
+    fn from(num: i64) -> Self {
+        Self { num, denom: 1 }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.denom)
+        }
+    }
+}
+
+impl FromStr for Rational {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((n, d)) => {
+                let num: i64 = n.parse().map_err(|_| format!("bad rational: {}", s))?;
+                let denom: u64 = d.parse().map_err(|_| format!("bad rational: {}", s))?;
+                Self::new(num, denom).ok_or_else(|| format!("zero denominator: {}", s))
+            }
+            None => {
+                let num: i64 = s.parse().map_err(|_| format!("bad rational: {}", s))?;
+                Ok(Self::from(num))
+            }
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let lhs = self.num as i128 * other.denom as i128;
+        let rhs = other.num as i128 * self.denom as i128;
+        lhs.cmp(&rhs)
+    }
+}
+
+/// Implemented by [`Language`]s whose nodes can be interpreted as
+/// `+ - * / ^` over [`Rational`] constants, so that [`ConstantFold`] can
+/// fold them without knowing anything else about the language.
+pub trait RationalLanguage: Language {
+    /// Returns the constant this node represents, if it's a numeric leaf.
+    fn as_constant(&self) -> Option<Rational>;
+
+    /// Builds a numeric leaf node for `value`.
+    fn from_constant(value: Rational) -> Self;
+
+    /// Evaluates this operator over already-constant `args`, or `None` if
+    /// it isn't an arithmetic operator or the arguments are out of domain
+    /// (e.g. division by zero).
+    fn eval(&self, args: &[Rational]) -> Option<Rational>;
+}
+
+/// A reusable constant-folding [`Metadata`] over [`Rational`], for any
+/// [`Language`] implementing [`RationalLanguage`].
+///
+/// This is the `Option<i32>` shape from [`Metadata`]'s own docs, generalized
+/// to exact rationals: `make` evaluates an enode once all of its children
+/// have folded to a constant, and `merge` keeps whichever side already has
+/// one, asserting the two agree when both do.
+///
+/// [`Metadata`]: trait.Metadata.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConstantFold(pub Option<Rational>);
+
+impl<L: RationalLanguage> Metadata<L> for ConstantFold {
+    type Error = std::convert::Infallible;
+
+    fn merge(&self, other: &Self) -> (Self, DidMerge) {
+        let (merged, did_merge) = merge_option(self.0, other.0, |a, b| {
+            assert_eq!(a, b, "constant folding disagreement");
+            (a, DidMerge(false, false))
+        });
+        (Self(merged), did_merge)
+    }
+
+    fn make(enode: ENode<L, &Self>) -> Self {
+        if let Some(c) = enode.op.as_constant() {
+            return Self(Some(c));
+        }
+        let args: Option<Vec<Rational>> = enode.children.iter().map(|m| m.0).collect();
+        Self(args.and_then(|a| enode.op.eval(&a)))
+    }
+
+    fn modify(eclass: &mut EClass<L, Self>) {
+        if let Some(r) = eclass.metadata.0 {
+            eclass.nodes.push(ENode::leaf(L::from_constant(r)));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::Debug;
+
+use crate::DidMerge;
+
+/// A type usable as an index into a [`UnionFind`].
+pub trait Key: Copy + Eq + Debug {
+    /// Converts this key to a dense `usize` index.
+    fn to_index(self) -> usize;
+    /// Builds a key from a dense `usize` index.
+    fn from_index(index: usize) -> Self;
+}
+
+/// A value that lives behind a [`UnionFind`] key.
+///
+/// [`Value::merge`] combines the payloads of two classes whose keys are
+/// unioning. It returns the merged value along with a [`DidMerge`], which
+/// the [`UnionFind`] uses to decide whether the union needs to ripple any
+/// further (e.g. re-canonicalizing and re-analyzing parents).
+pub trait Value: Sized {
+    /// Unused for now, probably just make this `std::convert::Infallible`.
+    type Error: Debug;
+
+    /// Merges `from` into `to`, returning the merged value and a
+    /// [`DidMerge`] recording whether it changed relative to each side.
+    fn merge<K: Key>(
+        unionfind: &mut UnionFind<K, Self>,
+        to: Self,
+        from: Self,
+    ) -> Result<(Self, DidMerge), Self::Error>;
+}
+
+/// A union-find (disjoint-set) mapping keys to canonical representatives,
+/// each canonical key carrying a [`Value`] payload.
+#[derive(Debug, Clone)]
+pub struct UnionFind<K, V> {
+    parents: Vec<K>,
+    values: Vec<Option<V>>,
+    /// Canonical keys whose payload changed relative to what it replaced
+    /// (a [`DidMerge`] with a `true` first flag) and haven't yet been
+    /// drained by the egraph's rebuild loop, which re-canonicalizes and
+    /// re-analyzes their parents.
+    dirty: Vec<K>,
+}
+
+impl<K, V> Default for UnionFind<K, V> {
+    fn default() -> Self {
+        Self {
+            parents: Vec::new(),
+            values: Vec::new(),
+            dirty: Vec::new(),
+        }
+    }
+}
+
+impl<K: Key, V> UnionFind<K, V> {
+    /// Allocates a new key in its own singleton class, holding `value`.
+    pub fn make_set(&mut self, value: V) -> K {
+        let key = K::from_index(self.parents.len());
+        self.parents.push(key);
+        self.values.push(Some(value));
+        key
+    }
+
+    /// Finds the canonical key for `key`, compressing the path.
+    pub fn find(&mut self, key: K) -> K {
+        let parent = self.parents[key.to_index()];
+        if parent.to_index() == key.to_index() {
+            key
+        } else {
+            let root = self.find(parent);
+            self.parents[key.to_index()] = root;
+            root
+        }
+    }
+
+    /// Returns a reference to the payload of `key`'s class.
+    pub fn get(&self, key: K) -> &V {
+        self.values[key.to_index()].as_ref().unwrap()
+    }
+
+    /// Drains and returns the keys enqueued by unions whose merged payload
+    /// changed relative to the surviving side.
+    pub fn take_dirty(&mut self) -> Vec<K> {
+        std::mem::take(&mut self.dirty)
+    }
+}
+
+impl<K: Key, V: Value> UnionFind<K, V> {
+    /// Unions the classes of `a` and `b`, merging their payloads with
+    /// [`Value::merge`].
+    ///
+    /// Returns the new canonical key and the [`DidMerge`] from the merge.
+    /// Only enqueues the result onto the dirty worklist (for the rebuild
+    /// loop to re-canonicalize and re-analyze parents) when the merged
+    /// payload actually changed relative to the surviving side; a union
+    /// whose `DidMerge` is `(false, false)` leaves the worklist untouched,
+    /// letting the rebuild loop short-circuit.
+    pub fn union(&mut self, a: K, b: K) -> Result<(K, DidMerge), V::Error> {
+        let ra = self.find(a);
+        let rb = self.find(b);
+
+        if ra.to_index() == rb.to_index() {
+            return Ok((ra, DidMerge(false, false)));
+        }
+
+        let to = self.values[ra.to_index()].take().unwrap();
+        let from = self.values[rb.to_index()].take().unwrap();
+
+        let (merged, did_merge) = V::merge(self, to, from)?;
+        self.parents[rb.to_index()] = ra;
+        self.values[ra.to_index()] = Some(merged);
+
+        if did_merge.0 {
+            self.dirty.push(ra);
+        }
+
+        Ok((ra, did_merge))
+    }
+}
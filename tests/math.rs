@@ -1,12 +1,11 @@
-use egg::{rewrite as rw, *};
+use egg::{rewrite as rw, Rational, *};
 
 use log::trace;
-use ordered_float::NotNan;
 
 pub type EGraph = egg::EGraph<Math, Meta>;
 pub type Rewrite = egg::Rewrite<Math, Meta>;
 
-type Constant = NotNan<f64>;
+type Constant = Rational;
 
 define_language! {
     pub enum Math {
@@ -55,12 +54,12 @@ pub struct Meta {
 fn eval(op: Math, args: &[Constant]) -> Option<Constant> {
     let a = |i| args.get(i).cloned();
     trace!("{} {:?} = ...", op, args);
-    let zero = Some(0.0.into());
     let res = match op {
-        Math::Add => Some(a(0)? + a(1)?),
-        Math::Sub => Some(a(0)? - a(1)?),
-        Math::Mul => Some(a(0)? * a(1)?),
-        Math::Div if a(1) != zero => Some(a(0)? / a(1)?),
+        Math::Add => a(0)?.checked_add(a(1)?),
+        Math::Sub => a(0)?.checked_sub(a(1)?),
+        Math::Mul => a(0)?.checked_mul(a(1)?),
+        Math::Div => a(0)?.checked_div(a(1)?),
+        Math::Pow => a(1)?.to_i64().and_then(|exp| a(0)?.checked_pow(exp)),
         _ => None,
     };
     trace!("{} {:?} = {:?}", op, args, res);
@@ -69,12 +68,15 @@ fn eval(op: Math, args: &[Constant]) -> Option<Constant> {
 
 impl Metadata<Math> for Meta {
     type Error = std::convert::Infallible;
-    fn merge(&self, other: &Self) -> Self {
-        if self.cost <= other.cost {
-            self.clone()
+    fn merge(&self, other: &Self) -> (Self, DidMerge) {
+        let (cost, did_merge) = merge_min(self.cost, other.cost);
+        let best = if did_merge.0 {
+            other.best.clone()
         } else {
-            other.clone()
-        }
+            self.best.clone()
+        };
+        let did_merge = DidMerge(did_merge.0, did_merge.1 || best != other.best);
+        (Self { cost, best }, did_merge)
     }
 
     fn make(egraph: &EGraph, enode: &ENode<Math>) -> Self {
@@ -122,7 +124,7 @@ fn c_is_const_or_var_and_not_x(egraph: &mut EGraph, _: Id, subst: &Subst) -> boo
 
 fn is_not_zero(var: &'static str) -> impl Fn(&mut EGraph, Id, &Subst) -> bool {
     let var = var.parse().unwrap();
-    let zero = enode!(Math::Constant(0.0.into()));
+    let zero = enode!(Math::Constant(0.into()));
     move |egraph, _, subst| !egraph[subst[&var]].nodes.contains(&zero)
 }
 
@@ -0,0 +1,187 @@
+use egg::{rewrite as rw, *};
+
+use log::trace;
+
+pub type EGraph = egg::EGraph<Solve, Meta>;
+pub type Rewrite = egg::Rewrite<Solve, Meta>;
+
+type Constant = Rational;
+
+/// The name of the designated "unknown" variable that [`solve`] isolates.
+const UNKNOWN: &str = "x";
+
+define_language! {
+    pub enum Solve {
+        Equals = "=",
+        Add = "+",
+        Sub = "-",
+        Mul = "*",
+        Div = "/",
+        Constant(Constant),
+        Variable(String),
+    }
+}
+
+struct SolveCostFn;
+impl egg::CostFunction<Solve> for SolveCostFn {
+    type Cost = usize;
+    fn cost(&mut self, enode: &ENode<Solve, Self::Cost>) -> Self::Cost {
+        1 + enode.children.iter().sum::<usize>()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meta {
+    pub cost: usize,
+    pub best: RecExpr<Solve>,
+    /// Whether [`UNKNOWN`] occurs anywhere beneath this eclass.
+    pub has_unknown: bool,
+}
+
+fn eval(op: Solve, args: &[Constant]) -> Option<Constant> {
+    let a = |i| args.get(i).cloned();
+    trace!("{} {:?} = ...", op, args);
+    let res = match op {
+        Solve::Add => a(0)?.checked_add(a(1)?),
+        Solve::Sub => a(0)?.checked_sub(a(1)?),
+        Solve::Mul => a(0)?.checked_mul(a(1)?),
+        Solve::Div => a(0)?.checked_div(a(1)?),
+        _ => None,
+    };
+    trace!("{} {:?} = {:?}", op, args, res);
+    res
+}
+
+impl Metadata<Solve> for Meta {
+    type Error = std::convert::Infallible;
+
+    fn merge(&self, other: &Self) -> (Self, DidMerge) {
+        let (cost, cost_changed) = merge_min(self.cost, other.cost);
+        let best = if cost_changed.0 {
+            other.best.clone()
+        } else {
+            self.best.clone()
+        };
+        let (has_unknown, unknown_changed) = merge_max(self.has_unknown, other.has_unknown);
+        let did_merge = DidMerge(
+            cost_changed.0 || unknown_changed.0,
+            cost_changed.1 || unknown_changed.1 || best != other.best,
+        );
+        (
+            Self {
+                cost,
+                best,
+                has_unknown,
+            },
+            did_merge,
+        )
+    }
+
+    fn make(egraph: &EGraph, enode: &ENode<Solve>) -> Self {
+        let meta = |i: Id| &egraph[i].metadata;
+        let has_unknown = match &enode.op {
+            Solve::Variable(s) if s == UNKNOWN => true,
+            _ => enode.children.iter().any(|&c| meta(c).has_unknown),
+        };
+
+        let enode = {
+            let const_args: Option<Vec<Constant>> = enode
+                .children
+                .iter()
+                .map(|id| match meta(*id).best.as_ref().op {
+                    Solve::Constant(c) => Some(c),
+                    _ => None,
+                })
+                .collect();
+
+            const_args
+                .and_then(|a| eval(enode.op.clone(), &a))
+                .map(|c| ENode::leaf(Solve::Constant(c)))
+                .unwrap_or_else(|| enode.clone())
+        };
+
+        let best: RecExpr<_> = enode.map_children(|c| meta(c).best.clone()).into();
+        let cost = SolveCostFn.cost(&enode.map_children(|c| meta(c).cost));
+        Self {
+            best,
+            cost,
+            has_unknown,
+        }
+    }
+
+    fn modify(eclass: &mut EClass<Solve, Self>) {
+        let best = eclass.metadata.best.as_ref();
+        if best.children.is_empty() {
+            eclass.nodes = vec![ENode::leaf(best.op.clone())]
+        }
+    }
+}
+
+/// Extracts a solution from `root`'s eclass using a caller-supplied
+/// `solved` predicate.
+///
+/// `solved` inspects a candidate enode and, if its structure "solves" the
+/// unknown (e.g. an `(= x <rhs>)` shape), returns the [`Id`] of the
+/// ground-term side; any other enode should yield `None`. Among every side
+/// `solved` returns whose metadata reports no occurrence of the unknown
+/// (per [`Meta::has_unknown`]), `solve` picks the cheapest
+/// (per [`Meta::cost`]) and returns its best expression. Returns `None` if
+/// no candidate isolates the unknown.
+pub fn solve(
+    egraph: &EGraph,
+    root: Id,
+    solved: impl Fn(&ENode<Solve>) -> Option<Id>,
+) -> Option<RecExpr<Solve>> {
+    egraph[egraph.find(root)]
+        .nodes
+        .iter()
+        .filter_map(|n| solved(n))
+        .map(|side| egraph.find(side))
+        .filter(|&side| !egraph[side].metadata.has_unknown)
+        .min_by_key(|&side| egraph[side].metadata.cost)
+        .map(|side| egraph[side].metadata.best.clone())
+}
+
+/// A [`solve`] predicate for `(= lhs rhs)` shapes, returning whichever side
+/// isn't `unknown`.
+pub fn equals_unknown(egraph: &EGraph, unknown: Id) -> impl Fn(&ENode<Solve>) -> Option<Id> + '_ {
+    let unknown = egraph.find(unknown);
+    move |n| {
+        if n.op != Solve::Equals {
+            return None;
+        }
+        let lhs = egraph.find(n.children[0]);
+        let rhs = egraph.find(n.children[1]);
+        if lhs == unknown {
+            Some(rhs)
+        } else if rhs == unknown {
+            Some(lhs)
+        } else {
+            None
+        }
+    }
+}
+
+#[rustfmt::skip]
+pub fn rules() -> Vec<Rewrite> { vec![
+    rw!("comm-eq";    "(= ?a ?b)"        => "(= ?b ?a)"),
+    rw!("move-add-l"; "(= (+ ?a ?b) ?c)" => "(= ?a (- ?c ?b))"),
+    rw!("move-add-r"; "(= (+ ?a ?b) ?c)" => "(= ?b (- ?c ?a))"),
+    rw!("move-sub";   "(= (- ?a ?b) ?c)" => "(= ?a (+ ?c ?b))"),
+    rw!("move-mul";   "(= (* ?a ?b) ?c)" => "(= ?a (/ ?c ?b))"),
+]}
+
+#[test]
+fn solve_linear() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let start = &"(= (+ x 2) 5)".parse().unwrap();
+    let mut egraph = EGraph::default();
+    let root = egraph.add_expr(start);
+    let unknown = egraph.add_expr(&UNKNOWN.parse().unwrap());
+
+    let runner = Runner::new().with_egraph(egraph).run(&rules());
+    let solved = equals_unknown(&runner.egraph, unknown);
+    let solution = solve(&runner.egraph, root, solved).unwrap();
+    assert_eq!(solution, "3".parse().unwrap());
+}